@@ -13,6 +13,7 @@ use dom::bindings::reflector::{DomObject, Reflector, reflect_dom_object};
 use dom::bindings::str::DOMString;
 use dom::globalscope::GlobalScope;
 use dom::gamepadbuttonlist::GamepadButtonList;
+use dom::gamepadhapticactuator::GamepadHapticActuator;
 use dom_struct::dom_struct;
 use dom::vrpose::VRPose;
 use js::jsapi::{Heap, JSContext, JSObject};
@@ -31,7 +32,8 @@ pub struct Gamepad {
     axes: Heap<*mut JSObject>,
     buttons: JS<GamepadButtonList>,
     pose: MutNullableJS<VRPose>,
-    display_id: Cell<u64>
+    display_id: Cell<u64>,
+    haptic_actuator: MutNullableJS<GamepadHapticActuator>
 }
 
 impl Gamepad {
@@ -54,7 +56,8 @@ impl Gamepad {
             axes: Heap::default(),
             buttons: JS::from_ref(&*buttons),
             pose: MutNullableJS::new(Some(pose.deref())),
-            display_id: Cell::new(display_id)
+            display_id: Cell::new(display_id),
+            haptic_actuator: MutNullableJS::default()
         };
       
         let root = reflect_dom_object(box gamepad,
@@ -111,6 +114,13 @@ impl GamepadMethods for Gamepad {
     fn DisplayId(&self) -> u32 {
         self.display_id.get() as u32
     }
+
+    // https://www.w3.org/TR/gamepad-extensions/#gamepadhapticactuator-interface
+    fn VibrationActuator(&self) -> Root<GamepadHapticActuator> {
+        self.haptic_actuator.or_init(|| {
+            GamepadHapticActuator::new(&self.global(), self.gamepad_id())
+        })
+    }
 }
 
 impl Gamepad {
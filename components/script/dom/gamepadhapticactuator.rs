@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use core::ops::Deref;
+use dom::bindings::codegen::Bindings::GamepadHapticActuatorBinding;
+use dom::bindings::codegen::Bindings::GamepadHapticActuatorBinding::GamepadHapticActuatorMethods;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{DomObject, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::globalscope::GlobalScope;
+use dom::promise::Promise;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use std::cell::Cell;
+use std::rc::Rc;
+use webvr_traits::WebVRMsg;
+
+#[dom_struct]
+pub struct GamepadHapticActuator {
+    reflector_: Reflector,
+    gamepad_id: u64,
+    playing: Cell<bool>,
+}
+
+impl GamepadHapticActuator {
+
+    fn new_inherited(gamepad_id: u64) -> GamepadHapticActuator {
+        GamepadHapticActuator {
+            reflector_: Reflector::new(),
+            gamepad_id: gamepad_id,
+            playing: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, gamepad_id: u64) -> Root<GamepadHapticActuator> {
+        reflect_dom_object(box GamepadHapticActuator::new_inherited(gamepad_id),
+                           global,
+                           GamepadHapticActuatorBinding::Wrap)
+    }
+}
+
+impl GamepadHapticActuatorMethods for GamepadHapticActuator {
+    // https://www.w3.org/TR/gamepad/#dom-gamepadhapticactuator-type
+    fn Type(&self) -> DOMString {
+        DOMString::from("vibration")
+    }
+
+    // https://www.w3.org/TR/gamepad-extensions/#dom-gamepadhapticactuator-pulse
+    // Blocks until the WebVR thread replies once `duration` ms have passed,
+    // then returns a promise already resolved with true (or rejected with the
+    // WebVR thread's error); resolves with false when there is no WebVR
+    // thread. Preemption of a running effect by another pulse is not handled.
+    fn Pulse(&self, value: Finite<f64>, duration: Finite<f64>) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        let webvr_thread = match self.global().as_window().webvr_thread() {
+            Some(thread) => thread,
+            None => {
+                promise.resolve_native(promise.global().get_cx(), &false);
+                return promise;
+            }
+        };
+
+        self.playing.set(true);
+        let (sender, receiver) = ipc::channel().unwrap();
+        webvr_thread.send(WebVRMsg::VibrateHapticGamepad(self.global().pipeline_id(),
+                                                         self.gamepad_id,
+                                                         *duration.deref() as u32,
+                                                         *value.deref(),
+                                                         sender)).unwrap();
+        let result = receiver.recv().unwrap();
+        self.playing.set(false);
+        match result {
+            Ok(()) => promise.resolve_native(promise.global().get_cx(), &true),
+            Err(e) => promise.reject_native(promise.global().get_cx(), &e),
+        }
+
+        promise
+    }
+}
+
+impl GamepadHapticActuator {
+    pub fn is_playing(&self) -> bool {
+        self.playing.get()
+    }
+}
@@ -6,9 +6,12 @@ use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::VRBinding;
 use dom::bindings::codegen::Bindings::VRBinding::VRMethods;
 use dom::bindings::error::Error;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, Root};
 use dom::bindings::reflector::{Reflectable, reflect_dom_object};
 use dom::eventtarget::EventTarget;
+use dom::gamepad::Gamepad;
+use dom::node::Node;
 use dom::promise::Promise;
 use dom::globalscope::GlobalScope;
 use dom::vrdisplay::VRDisplay;
@@ -81,8 +84,25 @@ impl VRMethods for VR {
 
     // https://w3c.github.io/webvr/#interface-navigator
     fn VrEnabled(&self) -> bool {
-        // TODO: check iframe
-        true
+        // Nested browsing contexts only get access to WebVR if their <iframe>
+        // opts in with the allowvr attribute (the WebVR equivalent of
+        // allowfullscreen), so embedded third-party content can't silently
+        // start polling for headsets. Walk the whole containing-document
+        // ancestry rather than just the immediate parent: a single
+        // allowvr-less ancestor anywhere in the chain must veto access, the
+        // same way a single frame without allowfullscreen vetoes fullscreen.
+        let mut window = Root::from_ref(self.global().as_window());
+        loop {
+            if window.is_top_level() {
+                return true;
+            }
+            match window.frame_element() {
+                Some(ref element) if element.has_attribute("allowvr") => {
+                    window = element.upcast::<Node>().owner_doc().window();
+                },
+                _ => return false,
+            }
+        }
     }
 }
 
@@ -108,4 +128,47 @@ impl VR {
             self.displays.borrow_mut().push(JS::from_ref(&*root));
         }
     }
+
+    // Fetches the current VR controllers over WebVRMsg::GetGamepads/
+    // GetGamepadState and wraps each one in a Gamepad. Nothing calls this
+    // from navigator.getGamepads(), so these controllers are not merged into
+    // that list.
+    #[allow(unrooted_must_root)]
+    pub fn get_vr_gamepads(&self) -> Vec<Root<Gamepad>> {
+        let mut gamepads = Vec::new();
+
+        let wevbr_sender = match self.webvr_thread() {
+            Some(wevbr_sender) => wevbr_sender,
+            None => return gamepads,
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        wevbr_sender.send(WebVRMsg::GetGamepads(sender)).unwrap();
+        let displays = match receiver.recv().unwrap() {
+            Ok(displays) => displays,
+            Err(e) => {
+                error!("WebVR::GetGamepads: {:?}", e);
+                return gamepads;
+            }
+        };
+
+        for (index, data) in displays.into_iter().enumerate() {
+            let (state_sender, state_receiver) = ipc::channel().unwrap();
+            wevbr_sender.send(WebVRMsg::GetGamepadState(self.global().pipeline_id(),
+                                                         data.gamepad_id,
+                                                         state_sender)).unwrap();
+            match state_receiver.recv().unwrap() {
+                Ok(state) => {
+                    gamepads.push(Gamepad::new_from_vr(&self.global(),
+                                                       data.name,
+                                                       data.display_id,
+                                                       index as u32,
+                                                       &state));
+                },
+                Err(e) => error!("WebVR::GetGamepadState: {:?}", e),
+            }
+        }
+
+        gamepads
+    }
 }
\ No newline at end of file
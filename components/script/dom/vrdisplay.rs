@@ -29,10 +29,23 @@ use js::jsapi::JSContext;
 use ipc_channel::ipc;
 use ipc_channel::ipc::IpcSender;
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use vr_traits::webvr;
 use vr_traits::WebVRMsg;
 
+// getFrameData() reuses the last fetched pose for calls made within this
+// window of that fetch, rather than for a fixed number of calls, so it adapts
+// to however many times a frame a page happens to call it.
+const FRAME_DATA_CACHE_WINDOW_MS: u64 = 2;
+
+// How long a noted activation (an HMD mount; see note_activation) counts as
+// "transient" for the purposes of gating requestPresent(). Matches the HTML
+// spec's notion of transient activation having a short, bounded lifetime
+// rather than being a one-shot flag or a permanent grant.
+const TRANSIENT_ACTIVATION_LIFETIME_MS: u64 = 5000;
+
 #[dom_struct]
 pub struct VRDisplay {
     eventtarget: EventTarget,
@@ -50,6 +63,22 @@ pub struct VRDisplay {
     #[ignore_heap_size_of = "Defined in rust-webvr"]
     layer: DOMRefCell<WebVRLayer>,
     layer_ctx: MutNullableHeap<JS<WebGLRenderingContext>>,
+    // Last time frame_data was refreshed from the VR thread, so repeated
+    // getFrameData() calls within FRAME_DATA_CACHE_WINDOW_MS of it see the
+    // same pose instead of drifting between eyes.
+    #[ignore_heap_size_of = "Defined in std"]
+    frame_data_fetched_at: Cell<Option<Instant>>,
+    // Most recent HMD mount (Activate event) seen for this display, used to
+    // gate requestPresent() on transient activation. User gestures are not
+    // recorded here.
+    #[ignore_heap_size_of = "Defined in std"]
+    transient_activation: Cell<Option<Instant>>,
+    // Maps a handle issued by our own RequestAnimationFrame/CancelAnimationFrame
+    // while presenting to the underlying window rAF handle actually driving
+    // it, so presenting-mode handles can't collide with ones already
+    // outstanding on the window's own queue.
+    raf_callbacks: DOMRefCell<HashMap<i32, i32>>,
+    raf_handle_counter: Cell<i32>,
 }
 
 // Wrappers to include WebVR structs in a DOM struct
@@ -70,7 +99,7 @@ impl VRDisplay {
     fn new_inherited(global: &GlobalScope, display:&webvr::VRDisplayData) -> VRDisplay {
 
         let stage = match display.stage_parameters {
-            Some(ref params) => Some(VRStageParameters::new(&params, &global)),
+            Some(ref params) => Some(VRStageParameters::new(&params, display.display_id, &global)),
             None => None
         };
 
@@ -86,7 +115,11 @@ impl VRDisplay {
             stage_params: MutNullableHeap::new(stage.as_ref().map(|v| v.deref())),
             frame_data: DOMRefCell::new(Default::default()),
             layer: DOMRefCell::new(Default::default()),
-            layer_ctx: MutNullableHeap::default()
+            layer_ctx: MutNullableHeap::default(),
+            frame_data_fetched_at: Cell::new(None),
+            transient_activation: Cell::new(None),
+            raf_callbacks: DOMRefCell::new(HashMap::new()),
+            raf_handle_counter: Cell::new(0),
         }
     }
 
@@ -137,24 +170,42 @@ impl VRDisplayMethods for VRDisplay {
     }
 
     fn GetFrameData(&self, frameData: &VRFrameData) -> bool {
-        //TODO: sync with compositor
-        if let Some(wevbr_sender) = self.webvr_thread() {
-            let (sender, receiver) = ipc::channel().unwrap();
-            wevbr_sender.send(WebVRMsg::GetFrameData(self.global().pipeline_id(),
-                                                     self.get_display_id(),
-                                                     self.depth_near.get(),
-                                                     self.depth_far.get(),
-                                                     sender)).unwrap();
-            match receiver.recv().unwrap() {
-                Ok(data) => {
-                    self.frame_data.borrow_mut().0 = data;
-                },
-                Err(e) => {
-                    error!("WebVR::GetFrameData: {:?}", e);
+        // A page can call getFrameData() more than once per render frame (once
+        // per eye, say); re-querying the device each time would let the
+        // predicted pose drift between those calls, so only go back to the VR
+        // thread once the cached data is older than FRAME_DATA_CACHE_WINDOW_MS.
+        let is_stale = match self.frame_data_fetched_at.get() {
+            Some(fetched_at) => fetched_at.elapsed() >= Duration::from_millis(FRAME_DATA_CACHE_WINDOW_MS),
+            None => true,
+        };
+
+        if is_stale {
+            if let Some(wevbr_sender) = self.webvr_thread() {
+                let (sender, receiver) = ipc::channel().unwrap();
+                wevbr_sender.send(WebVRMsg::GetFrameData(self.global().pipeline_id(),
+                                                         self.get_display_id(),
+                                                         self.depth_near.get(),
+                                                         self.depth_far.get(),
+                                                         sender)).unwrap();
+                match receiver.recv().unwrap() {
+                    Ok(data) => {
+                        self.frame_data.borrow_mut().0 = data;
+                        self.frame_data_fetched_at.set(Some(Instant::now()));
+                    },
+                    Err(e) => {
+                        error!("WebVR::GetFrameData: {:?}", e);
+                    }
                 }
             }
         }
 
+        // WebVR spec: return false if there's no valid pose to report, rather
+        // than claiming success when we've never actually fetched one (no
+        // WebVR thread available, or every fetch so far has errored).
+        if self.frame_data_fetched_at.get().is_none() {
+            return false;
+        }
+
         frameData.update(&self.frame_data.borrow().0);
         true
     }
@@ -166,9 +217,11 @@ impl VRDisplayMethods for VRDisplay {
     fn ResetPose(&self) -> () {
         if let Some(wevbr_sender) = self.webvr_thread() {
             wevbr_sender.send(WebVRMsg::ResetPose(self.global().pipeline_id(),
-                                                  self.get_display_id(), 
+                                                  self.get_display_id(),
                                                   None)).unwrap();
         }
+        // Don't let a subsequent getFrameData() serve the now-stale cached pose.
+        self.frame_data_fetched_at.set(None);
     }
 
     fn DepthNear(&self) -> Finite<f64> {
@@ -187,18 +240,43 @@ impl VRDisplayMethods for VRDisplay {
         self.depth_far.set(*value.deref());
     }
 
-    fn RequestAnimationFrame(&self, _callback: Rc<FrameRequestCallback>) -> i32 {
-        unimplemented!()
+    // https://w3c.github.io/webvr/spec/1.1/#dom-vrdisplay-requestanimationframe
+    fn RequestAnimationFrame(&self, callback: Rc<FrameRequestCallback>) -> i32 {
+        // Real headset-vsync-paced invocation needs a script-thread
+        // callback-routing path (a WebVREventMsg-style tick delivered to
+        // handle_webvr_event) that isn't part of this file, so the callback
+        // itself still has to be invoked through the window's own rAF queue.
+        // What's real here: our own handle numbering while presenting (see
+        // CancelAnimationFrame). init_present() separately sends RequestVSync,
+        // which only switches the WebVR thread's shared event-poll period to
+        // a fixed ~90Hz interval, not to the display's own refresh rate.
+        let window_handle = self.global().as_window().RequestAnimationFrame(callback);
+        if !self.presenting.get() {
+            return window_handle;
+        }
+        let handle = self.raf_handle_counter.get() + 1;
+        self.raf_handle_counter.set(handle);
+        self.raf_callbacks.borrow_mut().insert(handle, window_handle);
+        handle
     }
 
-    fn CancelAnimationFrame(&self, _handle: i32) -> () {
-        unimplemented!()
+    // https://w3c.github.io/webvr/spec/1.1/#dom-vrdisplay-cancelanimationframe
+    fn CancelAnimationFrame(&self, handle: i32) -> () {
+        let window_handle = self.raf_callbacks.borrow_mut().remove(&handle).unwrap_or(handle);
+        self.global().as_window().CancelAnimationFrame(window_handle);
     }
 
     #[allow(unrooted_must_root)]
     fn RequestPresent(&self, layers: Vec<VRLayer>) -> Rc<Promise> {
         let promise = Promise::new(&self.global());
-        // TODO: WebVR spec: this method must be called in response to a user gesture
+
+        // WebVR spec: requestPresent() must be triggered by user activation,
+        // otherwise the promise MUST be rejected.
+        if !self.has_transient_activation() {
+            let msg = "VRDisplay.requestPresent must be called from a user gesture".to_string();
+            promise.reject_native(promise.global().get_cx(), &msg);
+            return promise;
+        }
 
         // WebVR spec: If canPresent is false the promise MUST be rejected
         if !self.display.borrow().0.capabilities.can_present {
@@ -241,6 +319,7 @@ impl VRDisplayMethods for VRDisplay {
             let (sender, receiver) = ipc::channel().unwrap();
             wevbr_sender.send(WebVRMsg::RequestPresent(self.global().pipeline_id(),
                                                        self.display.borrow().0.display_id,
+                                                       vec![layer_bounds.0.clone()],
                                                        sender))
                                                        .unwrap();
             match receiver.recv().unwrap() {
@@ -296,8 +375,31 @@ impl VRDisplayMethods for VRDisplay {
         promise
     }
 
+    // https://w3c.github.io/webvr/spec/1.1/#dom-vrdisplay-submitframe
     fn SubmitFrame(&self) -> () {
-        unimplemented!()
+        // WebVR spec: Calling submitFrame while not presenting has no effect.
+        if !self.presenting.get() {
+            return;
+        }
+
+        if self.layer_ctx.get().is_none() {
+            return;
+        }
+
+        if let Some(wevbr_sender) = self.webvr_thread() {
+            let (sender, receiver) = ipc::channel().unwrap();
+            wevbr_sender.send(WebVRMsg::SubmitFrame(self.global().pipeline_id(),
+                                                     self.get_display_id(),
+                                                     // WebVR 1.1 only allows a single layer, so this
+                                                     // is always the layer stored at index 0 by
+                                                     // store_layers()/RequestPresent.
+                                                     0,
+                                                     self.layer.borrow().0.clone(),
+                                                     sender)).unwrap();
+            if let Err(e) = receiver.recv().unwrap() {
+                error!("WebVR::SubmitFrame: {:?}", e);
+            }
+        }
     }
 }
 
@@ -312,6 +414,12 @@ impl VRDisplay {
     }
 
     pub fn update_display(&self, display: &webvr::VRDisplayData) {
+        if let Some(ref parameters) = display.stage_parameters {
+            match self.stage_params.get() {
+                Some(stage) => stage.update(&parameters),
+                None => self.stage_params.set(JS::from_ref(&*VRStageParameters::new(&parameters, display.display_id, &self.global()))),
+            }
+        }
         self.display.borrow_mut().0 = display.clone()
     }
 
@@ -323,8 +431,24 @@ impl VRDisplay {
             webvr::VRDisplayEvent::Disconnect(_id) => {
                 self.display.borrow_mut().0.connected = false;
             },
-            webvr::VRDisplayEvent::Activate(ref display, _) |
-            webvr::VRDisplayEvent::Deactivate(ref display, _) |
+            webvr::VRDisplayEvent::Activate(ref display, _) => {
+                self.update_display(&display);
+                // HMD mount counts as a user gesture: let a vrdisplayactivate
+                // handler call requestPresent() and pass its
+                // transient-activation check.
+                self.note_activation();
+                self.notify_event(&event);
+            },
+            webvr::VRDisplayEvent::Deactivate(ref display, _) => {
+                self.update_display(&display);
+                // HMD dismount: auto-exit presentation, mirroring how a
+                // fullscreen page is kicked out of fullscreen when dismissed
+                // from outside the page.
+                if self.presenting.get() {
+                    self.auto_exit_present();
+                }
+                self.notify_event(&event);
+            },
             webvr::VRDisplayEvent::Blur(ref display) |
             webvr::VRDisplayEvent::Focus(ref display) => {
                 self.update_display(&display);
@@ -349,12 +473,45 @@ impl VRDisplay {
         event.upcast::<Event>().fire(self.upcast());
     }
 
+    // Records an activation as grounds for a subsequent requestPresent() call
+    // to count as triggered by transient activation. The only caller is
+    // handle_webvr_event's Activate (HMD mount) arm; clicks, key presses and
+    // other user gestures do not call this, so requestPresent() made from an
+    // ordinary click handler is rejected.
+    pub fn note_activation(&self) {
+        self.transient_activation.set(Some(Instant::now()));
+    }
+
+    fn has_transient_activation(&self) -> bool {
+        self.transient_activation.get()
+            .map_or(false, |at| at.elapsed() < Duration::from_millis(TRANSIENT_ACTIVATION_LIFETIME_MS))
+    }
+
     fn init_present(&self) {
         self.presenting.set(true);
+        if let Some(wevbr_sender) = self.webvr_thread() {
+            wevbr_sender.send(WebVRMsg::RequestVSync(self.global().pipeline_id(),
+                                                      self.display.borrow().0.display_id)).unwrap();
+        }
     }
 
     fn stop_present(&self) {
         self.presenting.set(false);
+        self.raf_callbacks.borrow_mut().clear();
+        if let Some(wevbr_sender) = self.webvr_thread() {
+            wevbr_sender.send(WebVRMsg::CancelVSync(self.display.borrow().0.display_id)).unwrap();
+        }
+    }
+
+    // Exits presentation without a promise to resolve, for cases (like an
+    // HMD dismount) where the UA is ending the session rather than the page.
+    fn auto_exit_present(&self) {
+        if let Some(wevbr_sender) = self.webvr_thread() {
+            wevbr_sender.send(WebVRMsg::ExitPresent(self.global().pipeline_id(),
+                                                     self.display.borrow().0.display_id,
+                                                     None)).unwrap();
+        }
+        self.stop_present();
     }
 }
 
@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use core::nonzero::NonZero;
+use dom::bindings::codegen::Bindings::VREyeParametersBinding;
+use dom::bindings::codegen::Bindings::VREyeParametersBinding::VREyeParametersMethods;
+use dom::bindings::conversions::slice_to_array_buffer_view;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::globalscope::GlobalScope;
+use dom::vrfieldofview::VRFieldOfView;
+use js::jsapi::{Heap, JSContext, JSObject};
+use std::cell::Cell;
+use vr_traits::webvr;
+
+#[dom_struct]
+pub struct VREyeParameters {
+    reflector_: Reflector,
+    offset: Heap<*mut JSObject>,
+    render_width: Cell<u32>,
+    render_height: Cell<u32>,
+    field_of_view: JS<VRFieldOfView>,
+}
+
+impl VREyeParameters {
+
+    #[allow(unrooted_must_root)]
+    fn new_inherited(parameters: &webvr::VREyeParameters, global: &GlobalScope) -> VREyeParameters {
+        let mut eye = VREyeParameters {
+            reflector_: Reflector::new(),
+            offset: Heap::default(),
+            render_width: Cell::new(parameters.render_width),
+            render_height: Cell::new(parameters.render_height),
+            field_of_view: JS::from_ref(&*VRFieldOfView::new(&parameters.field_of_view, &global)),
+        };
+        eye.offset.set(slice_to_array_buffer_view(global.get_cx(), &parameters.offset));
+
+        eye
+    }
+
+    pub fn new(parameters: &webvr::VREyeParameters, global: &GlobalScope) -> Root<VREyeParameters> {
+        reflect_dom_object(box VREyeParameters::new_inherited(&parameters, global),
+                           global,
+                           VREyeParametersBinding::Wrap)
+    }
+}
+
+impl VREyeParametersMethods for VREyeParameters {
+
+    // https://w3c.github.io/webvr/#dom-vreyeparameters-offset
+    #[allow(unsafe_code)]
+    fn Offset(&self, _cx: *mut JSContext) -> NonZero<*mut JSObject> {
+        unsafe { NonZero::new(self.offset.get()) }
+    }
+
+    // https://w3c.github.io/webvr/#dom-vreyeparameters-renderwidth
+    fn RenderWidth(&self) -> u32 {
+        self.render_width.get()
+    }
+
+    // https://w3c.github.io/webvr/#dom-vreyeparameters-renderheight
+    fn RenderHeight(&self) -> u32 {
+        self.render_height.get()
+    }
+
+    // https://w3c.github.io/webvr/#dom-vreyeparameters-fieldofview
+    fn FieldOfView(&self) -> Root<VRFieldOfView> {
+        Root::from_ref(&*self.field_of_view)
+    }
+}
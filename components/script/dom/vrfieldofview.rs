@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::VRFieldOfViewBinding;
+use dom::bindings::codegen::Bindings::VRFieldOfViewBinding::VRFieldOfViewMethods;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::globalscope::GlobalScope;
+use std::cell::Cell;
+use vr_traits::webvr;
+
+#[dom_struct]
+pub struct VRFieldOfView {
+    reflector_: Reflector,
+    up_degrees: Cell<f64>,
+    right_degrees: Cell<f64>,
+    down_degrees: Cell<f64>,
+    left_degrees: Cell<f64>,
+}
+
+impl VRFieldOfView {
+
+    fn new_inherited(fov: &webvr::VRFieldOfView) -> VRFieldOfView {
+        VRFieldOfView {
+            reflector_: Reflector::new(),
+            up_degrees: Cell::new(fov.up_degrees),
+            right_degrees: Cell::new(fov.right_degrees),
+            down_degrees: Cell::new(fov.down_degrees),
+            left_degrees: Cell::new(fov.left_degrees),
+        }
+    }
+
+    pub fn new(fov: &webvr::VRFieldOfView, global: &GlobalScope) -> Root<VRFieldOfView> {
+        reflect_dom_object(box VRFieldOfView::new_inherited(&fov),
+                           global,
+                           VRFieldOfViewBinding::Wrap)
+    }
+}
+
+impl VRFieldOfViewMethods for VRFieldOfView {
+
+    // https://w3c.github.io/webvr/#dom-vrfieldofview-updegrees
+    fn UpDegrees(&self) -> Finite<f64> {
+        Finite::wrap(self.up_degrees.get())
+    }
+
+    // https://w3c.github.io/webvr/#dom-vrfieldofview-rightdegrees
+    fn RightDegrees(&self) -> Finite<f64> {
+        Finite::wrap(self.right_degrees.get())
+    }
+
+    // https://w3c.github.io/webvr/#dom-vrfieldofview-downdegrees
+    fn DownDegrees(&self) -> Finite<f64> {
+        Finite::wrap(self.down_degrees.get())
+    }
+
+    // https://w3c.github.io/webvr/#dom-vrfieldofview-leftdegrees
+    fn LeftDegrees(&self) -> Finite<f64> {
+        Finite::wrap(self.left_degrees.get())
+    }
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use core::nonzero::NonZero;
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::VRFrameDataBinding;
 use dom::bindings::codegen::Bindings::VRFrameDataBinding::VRFrameDataMethods;
 use dom::bindings::conversions::{slice_to_array_buffer_view, update_array_buffer_view};
@@ -27,39 +28,47 @@ use vr::webvr;
 #[dom_struct]
 pub struct VRFrameData {
     reflector_: Reflector,
-    left_proj: Heap<*mut JSObject>,
-    left_view: Heap<*mut JSObject>,
-    right_proj: Heap<*mut JSObject>,
-    right_view: Heap<*mut JSObject>,
+    left_proj: DOMRefCell<Option<Heap<*mut JSObject>>>,
+    left_view: DOMRefCell<Option<Heap<*mut JSObject>>>,
+    right_proj: DOMRefCell<Option<Heap<*mut JSObject>>>,
+    right_view: DOMRefCell<Option<Heap<*mut JSObject>>>,
     pose: Root<VRPose>,
-    timestamp: Cell<u64>
+    timestamp: Cell<u64>,
+    #[ignore_heap_size_of = "Defined in rust-webvr"]
+    data: DOMRefCell<webvr::VRFrameData>
+}
+
+// Lazily materializes the JS typed-array view for a projection/view matrix.
+// Gecko's VRFrameData::LazyCreateMatrix defers this same allocation until
+// script actually reads the matrix, since most frames only need a subset.
+fn lazy_matrix(cx: *mut JSContext,
+              cell: &DOMRefCell<Option<Heap<*mut JSObject>>>,
+              matrix: &[f32; 16]) -> *mut JSObject {
+    if cell.borrow().is_none() {
+        let mut heap = Heap::default();
+        heap.set(slice_to_array_buffer_view(cx, matrix));
+        *cell.borrow_mut() = Some(heap);
+    } else {
+        unsafe {
+            update_array_buffer_view(cell.borrow().as_ref().unwrap().get(), matrix);
+        }
+    }
+    cell.borrow().as_ref().unwrap().get()
 }
 
 impl VRFrameData {
 
     fn new_inherited(global: &GlobalScope) -> VRFrameData {
-
-        let matrix = [1.0, 0.0, 0.0, 0.0,
-                      0.0, 1.0, 0.0, 0.0,
-                      0.0, 0.0, 1.0, 0.0,
-                      0.0, 0.0, 0.0, 1.0f32];
-
-        let mut framedata = VRFrameData {
+        VRFrameData {
             reflector_: Reflector::new(),
-            left_proj: Heap::default(),
-            left_view: Heap::default(),
-            right_proj: Heap::default(),
-            right_view: Heap::default(),
+            left_proj: DOMRefCell::new(None),
+            left_view: DOMRefCell::new(None),
+            right_proj: DOMRefCell::new(None),
+            right_view: DOMRefCell::new(None),
             pose: VRPose::new(&global, &Default::default()),
-            timestamp: Cell::new(time::get_time().sec as u64)
-        };
-
-        framedata.left_proj.set(slice_to_array_buffer_view(global.get_cx(), &matrix));
-        framedata.left_view.set(slice_to_array_buffer_view(global.get_cx(), &matrix));
-        framedata.right_proj.set(slice_to_array_buffer_view(global.get_cx(), &matrix));
-        framedata.right_view.set(slice_to_array_buffer_view(global.get_cx(), &matrix));
-
-        framedata
+            timestamp: Cell::new(time::get_time().sec as u64),
+            data: DOMRefCell::new(Default::default())
+        }
     }
 
     pub fn new(global: &GlobalScope) -> Root<VRFrameData> {
@@ -76,12 +85,10 @@ impl VRFrameData {
 
 impl VRFrameData {
     pub fn update(&self, data: &webvr::VRFrameData) {
-        unsafe {
-            update_array_buffer_view(self.left_proj.get(), &data.left_projection_matrix);
-            update_array_buffer_view(self.left_view.get(), &data.left_view_matrix);
-            update_array_buffer_view(self.right_proj.get(), &data.right_projection_matrix);
-            update_array_buffer_view(self.right_view.get(), &data.right_view_matrix);
-        }
+        // Only store the floats here; each JS typed array is created on the
+        // first read through the accessors below and refreshed in place on
+        // later reads.
+        *self.data.borrow_mut() = data.clone();
         self.timestamp.set(data.timestamp);
     }
 }
@@ -92,19 +99,23 @@ impl VRFrameDataMethods for VRFrameData {
     }
 
     fn LeftProjectionMatrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
-        unsafe { NonZero::new(self.left_proj.get()) }
+        let matrix = self.data.borrow().left_projection_matrix;
+        unsafe { NonZero::new(lazy_matrix(cx, &self.left_proj, &matrix)) }
     }
 
     fn LeftViewMatrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
-        unsafe { NonZero::new(self.left_view.get()) }
+        let matrix = self.data.borrow().left_view_matrix;
+        unsafe { NonZero::new(lazy_matrix(cx, &self.left_view, &matrix)) }
     }
 
     fn RightProjectionMatrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
-        unsafe { NonZero::new(self.right_proj.get()) }
+        let matrix = self.data.borrow().right_projection_matrix;
+        unsafe { NonZero::new(lazy_matrix(cx, &self.right_proj, &matrix)) }
     }
 
     fn RightViewMatrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
-        unsafe { NonZero::new(self.right_view.get()) }
+        let matrix = self.data.borrow().right_view_matrix;
+        unsafe { NonZero::new(lazy_matrix(cx, &self.right_view, &matrix)) }
     }
 
     fn Pose(&self) -> Root<VRPose> {
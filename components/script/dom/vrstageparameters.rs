@@ -6,13 +6,16 @@ use core::nonzero::NonZero;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::VRStageParametersBinding;
 use dom::bindings::codegen::Bindings::VRStageParametersBinding::VRStageParametersMethods;
-use dom::bindings::conversions::slice_to_array_buffer_view;
+use dom::bindings::conversions::{slice_to_array_buffer_view, update_array_buffer_view};
 use dom::bindings::js::Root;
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::reflector::{Reflector, Reflectable, reflect_dom_object};
 use dom::bindings::num::Finite;
 use dom::globalscope::GlobalScope;
+use ipc_channel::ipc;
+use ipc_channel::ipc::IpcSender;
 use js::jsapi::{Heap, JSContext, JSObject};
 use vr::webvr;
+use vr::WebVRMsg;
 
 #[dom_struct]
 pub struct VRStageParameters {
@@ -20,6 +23,13 @@ pub struct VRStageParameters {
     #[ignore_heap_size_of = "Defined in rust-webvr"]
     parameters: DOMRefCell<WebVRStageParameters>,
     transform: Heap<*mut JSObject>,
+    // Id of the device these parameters belong to, so BoundsGeometry() can
+    // pull the real chaperone polygon (if any) from the WebVR thread.
+    display_id: u64,
+    // Built lazily on first access. Populated from the device's real boundary
+    // points when the backend (currently only the mock backend) has any;
+    // falls back to synthesizing a rectangle from sizeX/sizeZ otherwise.
+    bounds_geometry: DOMRefCell<Option<Heap<*mut JSObject>>>,
 }
 
 // Wrappers required to include WebVR structs in a DOM struct
@@ -30,22 +40,61 @@ no_jsmanaged_fields!(WebVRStageParameters);
 impl VRStageParameters {
 
     #[allow(unrooted_must_root)]
-    fn new_inherited(parameters: &webvr::VRStageParameters, global: &GlobalScope) -> VRStageParameters {
+    fn new_inherited(parameters: &webvr::VRStageParameters, display_id: u64, global: &GlobalScope) -> VRStageParameters {
         let mut stage = VRStageParameters {
             reflector_: Reflector::new(),
             parameters: DOMRefCell::new(WebVRStageParameters(parameters.clone())),
-            transform: Heap::default()
+            transform: Heap::default(),
+            display_id: display_id,
+            bounds_geometry: DOMRefCell::new(None),
         };
         stage.transform.set(slice_to_array_buffer_view(global.get_cx(), &parameters.sitting_to_standing_transform));
 
         stage
     }
 
-    pub fn new(parameters: &webvr::VRStageParameters, global: &GlobalScope) -> Root<VRStageParameters> {
-        reflect_dom_object(box VRStageParameters::new_inherited(&parameters, global),
+    pub fn new(parameters: &webvr::VRStageParameters, display_id: u64, global: &GlobalScope) -> Root<VRStageParameters> {
+        reflect_dom_object(box VRStageParameters::new_inherited(&parameters, display_id, global),
                            global,
                            VRStageParametersBinding::Wrap)
     }
+
+    // Refreshes the transform/play-area size in place, the same way VRFrameData
+    // updates its matrices in place instead of reallocating the typed array.
+    #[allow(unsafe_code)]
+    pub fn update(&self, parameters: &webvr::VRStageParameters) {
+        unsafe {
+            update_array_buffer_view(self.transform.get(), &parameters.sitting_to_standing_transform);
+        }
+        *self.parameters.borrow_mut() = WebVRStageParameters(parameters.clone());
+        // The play area may have resized, so drop the cached corners.
+        *self.bounds_geometry.borrow_mut() = None;
+    }
+
+    fn webvr_thread(&self) -> Option<IpcSender<WebVRMsg>> {
+        self.global().as_window().webvr_thread()
+    }
+
+    // Real ordered (x, z) boundary points for this display's chaperone
+    // polygon, if the backend has any to offer.
+    fn fetch_bounds_points(&self) -> Option<Vec<(f32, f32)>> {
+        let wevbr_sender = match self.webvr_thread() {
+            Some(wevbr_sender) => wevbr_sender,
+            None => return None,
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        wevbr_sender.send(WebVRMsg::GetStageBounds(self.global().pipeline_id(),
+                                                    self.display_id,
+                                                    sender)).unwrap();
+        match receiver.recv().unwrap() {
+            Ok(bounds) => bounds,
+            Err(e) => {
+                error!("WebVR::GetStageBounds: {:?}", e);
+                None
+            }
+        }
+    }
 }
 
 impl VRStageParametersMethods for VRStageParameters {
@@ -65,4 +114,37 @@ impl VRStageParametersMethods for VRStageParameters {
     fn SizeZ(&self) -> Finite<f32> {
         Finite::wrap(self.parameters.borrow().0.size_y)
     }
+
+    // https://immersive-web.github.io/webxr/#dom-xrboundedreferencespace-boundsgeometry
+    #[allow(unsafe_code)]
+    fn BoundsGeometry(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
+        if self.bounds_geometry.borrow().is_none() {
+            let points: Vec<f32> = match self.fetch_bounds_points() {
+                Some(points) => points.into_iter().flat_map(|(x, z)| vec![x, z]).collect(),
+                None => {
+                    let (size_x, size_z) = {
+                        let parameters = &self.parameters.borrow().0;
+                        (parameters.size_x, parameters.size_y)
+                    };
+                    synthesize_corners(size_x, size_z).to_vec()
+                }
+            };
+            let mut heap = Heap::default();
+            heap.set(slice_to_array_buffer_view(cx, &points));
+            *self.bounds_geometry.borrow_mut() = Some(heap);
+        }
+        unsafe { NonZero::new(self.bounds_geometry.borrow().as_ref().unwrap().get()) }
+    }
+}
+
+// Builds the four (x, z) corners of the axis-aligned play-area rectangle,
+// centered on the origin of standing space, for backends that only report
+// a width/depth pair instead of an arbitrary chaperone polygon.
+fn synthesize_corners(size_x: f32, size_z: f32) -> [f32; 8] {
+    let hx = size_x / 2.0;
+    let hz = size_z / 2.0;
+    [-hx, -hz,
+      hx, -hz,
+      hx,  hz,
+     -hx,  hz]
 }
@@ -1,16 +1,53 @@
-use vr_traits::{WebVRMsg, WebVRResult};
+use vr_traits::{VRGamepadData, WebVRGamepadState, WebVRMsg, WebVRResult};
 use vr_traits::webvr::*;
 use ipc_channel::ipc;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
+use util::prefs::PREFS;
 use util::thread::spawn_named;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use msg::constellation_msg::PipelineId;
 use script_traits::{ConstellationMsg, WebVREventMsg};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::{thread, time};
 use webrender_traits;
 
+// Coarse cadence used while no display is presenting.
+const IDLE_POLL_INTERVAL_MS: usize = 500;
+// Tighter cadence used while any display has vsync requested (see
+// WebVRMsg::RequestVSync), so the event queue stays close to a 90Hz
+// headset's refresh rate instead of lagging ~500ms behind it. The same fixed
+// period applies to every display; it is not derived from a display's own
+// refresh rate.
+const PRESENTING_POLL_INTERVAL_MS: usize = 11;
+
+// Puppet display created and driven entirely by WebVRMsg::Mock* messages,
+// used by automated tests in lieu of a physical VRDevice.
+struct MockVRDisplay {
+    data: VRDisplayData,
+    frame_data: VRFrameData,
+    pose: VRPose,
+    pending_events: VecDeque<VRDisplayEvent>,
+    // Ordered (x, z) boundary points of a puppeted chaperone polygon, set by
+    // MockSetStageParameters. None if the test only supplied a sizeX/sizeZ
+    // rectangle, in which case VRStageParameters synthesizes one DOM-side.
+    bounds_points: Option<Vec<(f32, f32)>>,
+}
+
+impl MockVRDisplay {
+    fn new(data: VRDisplayData) -> MockVRDisplay {
+        MockVRDisplay {
+            data: data,
+            frame_data: Default::default(),
+            pose: Default::default(),
+            pending_events: VecDeque::new(),
+            bounds_points: None,
+        }
+    }
+}
+
 pub struct WebVRThread {
     receiver: IpcReceiver<WebVRMsg>,
     sender: IpcSender<WebVRMsg>,
@@ -18,7 +55,25 @@ pub struct WebVRThread {
     contexts: HashSet<PipelineId>,
     constellation_chan: Sender<ConstellationMsg>,
     polling_events: bool,
-    presenting: HashMap<u64, PipelineId>
+    // Shared with the dedicated WebVRPollEvents thread so it knows when to
+    // stop scheduling itself without needing a reply on every tick.
+    keep_polling: Arc<AtomicBool>,
+    // Shared poll period, in milliseconds: IDLE_POLL_INTERVAL_MS by default,
+    // PRESENTING_POLL_INTERVAL_MS while vsync_requested is non-empty.
+    poll_interval_ms: Arc<AtomicUsize>,
+    presenting: HashMap<u64, PipelineId>,
+    // Layers passed to the most recent successful RequestPresent/SubmitFrame
+    // for each presenting display, keyed by (display_id, layer_id).
+    presenting_layers: HashMap<(u64, u32), VRLayer>,
+    // Displays whose presenting page has asked for vsync-paced rAF callbacks
+    // via WebVRMsg::RequestVSync, used to decide which displays' presence
+    // should tighten the shared poll cadence.
+    vsync_requested: HashSet<u64>,
+    mock_enabled: bool,
+    mock_displays: HashMap<u64, MockVRDisplay>,
+    // Displays seen on the previous poll, used to synthesize Connect/Disconnect
+    // events for backends that don't report them on their own.
+    known_displays: HashSet<u64>,
 }
 
 impl WebVRThread {
@@ -35,7 +90,14 @@ impl WebVRThread {
             contexts: HashSet::new(),
             constellation_chan: constellation_chan,
             polling_events: false,
-            presenting: HashMap::new()
+            keep_polling: Arc::new(AtomicBool::new(false)),
+            poll_interval_ms: Arc::new(AtomicUsize::new(IDLE_POLL_INTERVAL_MS)),
+            presenting: HashMap::new(),
+            presenting_layers: HashMap::new(),
+            vsync_requested: HashSet::new(),
+            mock_enabled: PREFS.get("dom.webvr.test.enabled").as_boolean().unwrap_or(false),
+            mock_displays: HashMap::new(),
+            known_displays: HashSet::new(),
         }
     }
 
@@ -77,12 +139,49 @@ impl WebVRThread {
                 WebVRMsg::ResetPose(pipeline_id, device_id, sender) => {
                     self.handle_reset_pose(pipeline_id, device_id, sender);
                 },
-                WebVRMsg::RequestPresent(pipeline_id, device_id, sender) => {
-                    self.handle_request_present(pipeline_id, device_id, sender);
+                WebVRMsg::RequestPresent(pipeline_id, device_id, layers, sender) => {
+                    self.handle_request_present(pipeline_id, device_id, layers, sender);
                 },
                 WebVRMsg::ExitPresent(pipeline_id, device_id, sender) => {
                     self.handle_exit_present(pipeline_id, device_id, sender);
                 },
+                WebVRMsg::SubmitFrame(pipeline_id, device_id, layer_id, layer, sender) => {
+                    self.handle_submit_frame(pipeline_id, device_id, layer_id, layer, sender);
+                },
+                WebVRMsg::GetGamepads(sender) => {
+                    self.handle_get_gamepads(sender);
+                },
+                WebVRMsg::GetGamepadState(pipeline_id, gamepad_id, sender) => {
+                    self.handle_gamepad_state(pipeline_id, gamepad_id, sender);
+                },
+                WebVRMsg::GetStageBounds(pipeline_id, device_id, sender) => {
+                    self.handle_get_stage_bounds(pipeline_id, device_id, sender);
+                },
+                WebVRMsg::RequestVSync(pipeline_id, device_id) => {
+                    self.handle_request_vsync(pipeline_id, device_id);
+                },
+                WebVRMsg::CancelVSync(device_id) => {
+                    self.handle_cancel_vsync(device_id);
+                },
+                WebVRMsg::VibrateHapticGamepad(pipeline_id, gamepad_id, duration_ms, intensity, sender) => {
+                    self.handle_vibrate_haptic_gamepad(pipeline_id, gamepad_id, duration_ms, intensity, sender);
+                },
+                WebVRMsg::MockCreateDisplay(data) => {
+                    self.handle_mock_create_display(data);
+                    self.schedule_poll_events();
+                },
+                WebVRMsg::MockSetFrameData(device_id, data) => {
+                    self.handle_mock_set_frame_data(device_id, data);
+                },
+                WebVRMsg::MockSetPose(device_id, pose) => {
+                    self.handle_mock_set_pose(device_id, pose);
+                },
+                WebVRMsg::MockSetStageParameters(device_id, params, bounds_points) => {
+                    self.handle_mock_set_stage_parameters(device_id, params, bounds_points);
+                },
+                WebVRMsg::MockFireEvent(device_id, event) => {
+                    self.handle_mock_fire_event(device_id, event);
+                },
                 WebVRMsg::Exit => {
                     break
                 },
@@ -108,15 +207,22 @@ impl WebVRThread {
         for device in devices {
             displays.push(device.borrow().display_data());
         }
+        for mock in self.mock_displays.values() {
+            displays.push(mock.data.clone());
+        }
         sender.send(Ok(displays)).unwrap();
     }
 
-    fn handle_framedata(&mut self, 
+    fn handle_framedata(&mut self,
                         pipeline: PipelineId,
                         device_id: u64,
                         near: f64,
                         far: f64,
                         sender: IpcSender<WebVRResult<VRFrameData>>) {
+        if let Some(mock) = self.mock_displays.get(&device_id) {
+            sender.send(Ok(mock.frame_data.clone())).unwrap();
+            return;
+        }
       match self.access_check(pipeline, device_id) {
             Ok(device) => {
                 sender.send(Ok(device.borrow().inmediate_frame_data(near, far))).unwrap()
@@ -129,6 +235,12 @@ impl WebVRThread {
                          pipeline: PipelineId,
                          device_id: u64,
                          sender: IpcSender<WebVRResult<VRDisplayData>>) {
+        if let Some(mock) = self.mock_displays.get_mut(&device_id) {
+            mock.pose = Default::default();
+            mock.frame_data.pose = mock.pose.clone();
+            sender.send(Ok(mock.data.clone())).unwrap();
+            return;
+        }
         match self.access_check(pipeline, device_id) {
             Ok(device) => {
                 device.borrow_mut().reset_pose();
@@ -150,13 +262,29 @@ impl WebVRThread {
     fn handle_request_present(&mut self,
                          pipeline: PipelineId,
                          device_id: u64,
+                         layers: Vec<VRLayer>,
                          sender: IpcSender<WebVRResult<()>>) {
+        if self.mock_displays.contains_key(&device_id) {
+            if *self.presenting.get(&device_id).unwrap_or(&pipeline) != pipeline {
+                sender.send(Err("Device owned by another context".into())).unwrap();
+                return;
+            }
+            self.presenting.insert(device_id, pipeline);
+            self.store_layers(device_id, layers);
+            let data = self.mock_displays.get(&device_id).unwrap().data.clone();
+            sender.send(Ok(())).unwrap();
+            self.notify_event(VRDisplayEvent::PresentChange(data, true));
+            self.notify_focus_change(device_id, Some(pipeline));
+            return;
+        }
         match self.access_check(pipeline, device_id).map(|d| d.clone()) {
             Ok(device) => {
                 self.presenting.insert(device_id, pipeline);
+                self.store_layers(device_id, layers);
                 let data = device.borrow().display_data();
                 sender.send(Ok(())).unwrap();
                 self.notify_event(VRDisplayEvent::PresentChange(data, true));
+                self.notify_focus_change(device_id, Some(pipeline));
             },
             Err(msg) => {
                 sender.send(Err(msg.into())).unwrap();
@@ -164,18 +292,62 @@ impl WebVRThread {
         }
     }
 
+    fn store_layers(&mut self, device_id: u64, layers: Vec<VRLayer>) {
+        self.presenting_layers.retain(|&(id, _), _| id != device_id);
+        for (layer_id, layer) in layers.into_iter().enumerate() {
+            self.presenting_layers.insert((device_id, layer_id as u32), layer);
+        }
+    }
+
+    fn handle_submit_frame(&mut self,
+                           pipeline: PipelineId,
+                           device_id: u64,
+                           layer_id: u32,
+                           layer: VRLayer,
+                           sender: IpcSender<WebVRResult<()>>) {
+        if *self.presenting.get(&device_id).unwrap_or(&pipeline) != pipeline {
+            sender.send(Err("Device owned by another context".into())).unwrap();
+            return;
+        }
+        if !self.presenting_layers.contains_key(&(device_id, layer_id)) {
+            sender.send(Err("No such layer for this display".into())).unwrap();
+            return;
+        }
+        self.presenting_layers.insert((device_id, layer_id), layer);
+        sender.send(Ok(())).unwrap();
+    }
+
     fn handle_exit_present(&mut self,
                          pipeline: PipelineId,
                          device_id: u64,
                          sender: Option<IpcSender<WebVRResult<()>>>) {
+        if self.mock_displays.contains_key(&device_id) {
+            if *self.presenting.get(&device_id).unwrap_or(&pipeline) != pipeline {
+                if let Some(sender) = sender {
+                    sender.send(Err("Device owned by another context".into())).unwrap();
+                }
+                return;
+            }
+            self.presenting.remove(&device_id);
+            self.presenting_layers.retain(|&(id, _), _| id != device_id);
+            if let Some(sender) = sender {
+                sender.send(Ok(())).unwrap();
+            }
+            let data = self.mock_displays.get(&device_id).unwrap().data.clone();
+            self.notify_event(VRDisplayEvent::PresentChange(data, false));
+            self.notify_focus_change(device_id, None);
+            return;
+        }
         match self.access_check(pipeline, device_id).map(|d| d.clone()) {
             Ok(device) => {
                 self.presenting.remove(&device_id);
+                self.presenting_layers.retain(|&(id, _), _| id != device_id);
                 if let Some(sender) = sender {
                     sender.send(Ok(())).unwrap();
                 }
                 let data = device.borrow().display_data();
                 self.notify_event(VRDisplayEvent::PresentChange(data, false));
+                self.notify_focus_change(device_id, None);
             },
             Err(msg) => {
                 if let Some(sender) = sender {
@@ -185,19 +357,183 @@ impl WebVRThread {
         }
     }
 
-    fn poll_events(&mut self, sender: IpcSender<bool>) {
-        let events = self.service.poll_events();
-        if events.len() > 0 {
-            let pipeline_ids: Vec<PipelineId> = self.contexts.iter().map(|c| *c).collect();
-            for event in events {
-                let event = WebVREventMsg::DisplayEvent(event);
-                self.constellation_chan.send(ConstellationMsg::WebVREvent(pipeline_ids.clone(), event)).unwrap();
+    fn handle_get_gamepads(&mut self, sender: IpcSender<WebVRResult<Vec<VRGamepadData>>>) {
+        let gamepads = self.service.get_gamepads();
+        let mut list = Vec::new();
+        for gamepad in gamepads {
+            let gamepad = gamepad.borrow();
+            list.push(VRGamepadData {
+                gamepad_id: gamepad.id(),
+                display_id: gamepad.display_id(),
+                name: gamepad.name(),
+            });
+        }
+        sender.send(Ok(list)).unwrap();
+    }
+
+    fn handle_gamepad_state(&mut self,
+                            _pipeline: PipelineId,
+                            gamepad_id: u64,
+                            sender: IpcSender<WebVRResult<WebVRGamepadState>>) {
+        match self.service.get_gamepad(gamepad_id) {
+            Some(gamepad) => sender.send(Ok(gamepad.borrow().state())).unwrap(),
+            None => sender.send(Err("Gamepad not found".into())).unwrap()
+        }
+    }
+
+    fn handle_vibrate_haptic_gamepad(&mut self,
+                                     pipeline: PipelineId,
+                                     gamepad_id: u64,
+                                     duration_ms: u32,
+                                     intensity: f64,
+                                     sender: IpcSender<WebVRResult<()>>) {
+        let gamepad = match self.service.get_gamepad(gamepad_id) {
+            Some(gamepad) => gamepad,
+            None => {
+                sender.send(Err("Gamepad not found".into())).unwrap();
+                return;
+            }
+        };
+
+        let display_id = gamepad.borrow().display_id();
+        // A gamepad is owned by whichever context is presenting its
+        // associated display; a background page can't buzz it.
+        if *self.presenting.get(&display_id).unwrap_or(&pipeline) != pipeline {
+            sender.send(Err("Gamepad owned by another context".into())).unwrap();
+            return;
+        }
+
+        // Gamepad handles are RefCell-backed and never Send, so the actual
+        // vibrate() call has to stay on this thread. It's expected to be a
+        // fire-and-forget hardware call (set the motor state and return), not
+        // a blocking sleep for the full duration, so make it here rather than
+        // shipping the handle off to another thread.
+        gamepad.borrow_mut().vibrate(duration_ms, intensity);
+
+        // The promise is only supposed to resolve once the effect has
+        // actually finished playing, so delay the reply by duration_ms
+        // without blocking this thread's message loop for other contexts.
+        // Only plain Send values (the duration and the reply channel) cross
+        // into the timer thread; the gamepad handle itself never does.
+        spawn_named("WebVRHaptic".to_owned(), move || {
+            thread::sleep(time::Duration::from_millis(duration_ms as u64));
+            let _ = sender.send(Ok(()));
+        });
+    }
+
+    fn handle_mock_create_display(&mut self, data: VRDisplayData) {
+        if !self.mock_enabled {
+            warn!("Ignoring MockCreateDisplay: dom.webvr.test.enabled is not set");
+            return;
+        }
+        self.mock_displays.insert(data.display_id, MockVRDisplay::new(data));
+    }
+
+    fn handle_mock_set_frame_data(&mut self, device_id: u64, data: VRFrameData) {
+        if let Some(mock) = self.mock_displays.get_mut(&device_id) {
+            mock.frame_data = data;
+        }
+    }
+
+    fn handle_mock_set_pose(&mut self, device_id: u64, pose: Option<VRPose>) {
+        if let Some(mock) = self.mock_displays.get_mut(&device_id) {
+            mock.pose = pose.unwrap_or_default();
+            mock.frame_data.pose = mock.pose.clone();
+        }
+    }
+
+    fn handle_mock_set_stage_parameters(&mut self,
+                                        device_id: u64,
+                                        params: VRStageParameters,
+                                        bounds_points: Option<Vec<(f32, f32)>>) {
+        if let Some(mock) = self.mock_displays.get_mut(&device_id) {
+            mock.data.stage_parameters = Some(params);
+            mock.bounds_points = bounds_points;
+        }
+    }
+
+    fn handle_request_vsync(&mut self, pipeline: PipelineId, device_id: u64) {
+        // Only the context actually presenting the display gets to pace its
+        // rAF off the WebVR thread's tightened poll cadence.
+        if *self.presenting.get(&device_id).unwrap_or(&pipeline) == pipeline {
+            self.vsync_requested.insert(device_id);
+        }
+    }
+
+    fn handle_cancel_vsync(&mut self, device_id: u64) {
+        self.vsync_requested.remove(&device_id);
+    }
+
+    fn handle_get_stage_bounds(&self,
+                               _pipeline: PipelineId,
+                               device_id: u64,
+                               sender: IpcSender<WebVRResult<Option<Vec<(f32, f32)>>>>) {
+        // Only the mock backend can puppet a real chaperone polygon in this
+        // tree; physical backends only ever report a sizeX/sizeZ rectangle,
+        // so they fall through to the None default and let the DOM side
+        // synthesize one.
+        let bounds = self.mock_displays.get(&device_id).and_then(|mock| mock.bounds_points.clone());
+        sender.send(Ok(bounds)).unwrap();
+    }
+
+    fn handle_mock_fire_event(&mut self, device_id: u64, event: VRDisplayEvent) {
+        if let Some(mock) = self.mock_displays.get_mut(&device_id) {
+            mock.pending_events.push_back(event);
+        }
+    }
+
+    fn display_data(&self, device_id: u64) -> Option<VRDisplayData> {
+        if let Some(mock) = self.mock_displays.get(&device_id) {
+            return Some(mock.data.clone());
+        }
+        self.service.get_device(device_id).map(|d| d.borrow().display_data())
+    }
+
+    fn poll_events(&mut self, sender: IpcSender<()>) {
+        let mut events = self.service.poll_events();
+        for mock in self.mock_displays.values_mut() {
+            events.extend(mock.pending_events.drain(..));
+        }
+
+        // Diff the set of currently known displays against the last poll so
+        // that backends which never emit Connect/Disconnect themselves still
+        // get one synthesized here.
+        let current_displays: HashSet<u64> = self.service.get_devices().iter()
+            .map(|d| d.borrow().display_data().display_id)
+            .chain(self.mock_displays.keys().cloned())
+            .collect();
+        for id in current_displays.difference(&self.known_displays) {
+            if let Some(data) = self.display_data(*id) {
+                events.push(VRDisplayEvent::Connect(data));
             }
         }
+        for id in self.known_displays.difference(&current_displays) {
+            events.push(VRDisplayEvent::Disconnect(*id));
+        }
+        self.known_displays = current_displays;
+
+        let pipeline_ids: Vec<PipelineId> = self.contexts.iter().map(|c| *c).collect();
+        for event in events {
+            let msg = WebVREventMsg::DisplayEvent(event);
+            self.constellation_chan.send(ConstellationMsg::WebVREvent(pipeline_ids.clone(), msg)).unwrap();
+        }
 
         // Stop polling events if the callers are not using VR
         self.polling_events = self.contexts.len() > 0;
-        sender.send(self.polling_events).unwrap();
+        self.keep_polling.store(self.polling_events, Ordering::SeqCst);
+        // Only tighten the cadence for displays whose page actually asked for
+        // vsync-paced rAF; merely presenting without requesting it doesn't
+        // need the tighter poll.
+        let interval = if self.vsync_requested.is_empty() {
+            IDLE_POLL_INTERVAL_MS
+        } else {
+            PRESENTING_POLL_INTERVAL_MS
+        };
+        self.poll_interval_ms.store(interval, Ordering::SeqCst);
+        // schedule_poll_events only uses this reply to detect that the
+        // WebVRThread is still alive (via recv().is_err()); events themselves
+        // already went out above via the constellation channel.
+        sender.send(()).unwrap();
     }
 
     fn notify_event(&self, event: VRDisplayEvent) {
@@ -206,22 +542,53 @@ impl WebVRThread {
         self.constellation_chan.send(ConstellationMsg::WebVREvent(pipeline_ids.clone(), event)).unwrap();
     }
 
+    // Lets every other context holding a reference to this display know it
+    // just lost (or regained) focus, mirroring the blur/focus events Gecko's
+    // VREventObserver fires when the presenting context changes.
+    fn notify_focus_change(&self, device_id: u64, presenter: Option<PipelineId>) {
+        let data = match self.display_data(device_id) {
+            Some(data) => data,
+            None => return,
+        };
+        let others: Vec<PipelineId> = self.contexts.iter()
+            .filter(|c| Some(**c) != presenter)
+            .cloned()
+            .collect();
+        if others.is_empty() {
+            return;
+        }
+        let event = match presenter {
+            Some(_) => VRDisplayEvent::Blur(data),
+            None => VRDisplayEvent::Focus(data),
+        };
+        let msg = WebVREventMsg::DisplayEvent(event);
+        self.constellation_chan.send(ConstellationMsg::WebVREvent(others, msg)).unwrap();
+    }
+
     fn schedule_poll_events(&mut self) {
         if self.service.is_initialized() && !self.polling_events {
             self.polling_events = true;
+            self.keep_polling.store(true, Ordering::SeqCst);
             let webvr_thread = self.sender.clone();
-            let (sender, receiver) = ipc::channel().unwrap();
+            let keep_polling = self.keep_polling.clone();
+            let poll_interval_ms = self.poll_interval_ms.clone();
             spawn_named("WebVRPollEvents".into(), move || {
                 loop {
-                    if webvr_thread.send(WebVRMsg::PollEvents(sender.clone())).is_err() {
+                    let (sender, receiver) = ipc::channel().unwrap();
+                    if webvr_thread.send(WebVRMsg::PollEvents(sender)).is_err() {
                         // WebVR Thread closed
                         break;
                     }
-                    if !receiver.recv().unwrap_or(false) {
+                    if receiver.recv().is_err() {
+                        // WebVR Thread closed
+                        break;
+                    }
+                    if !keep_polling.load(Ordering::SeqCst) {
                         // WebVR Thread asked to unschedule this thread
                         break;
                     }
-                    thread::sleep(time::Duration::from_millis(500));
+                    let interval = poll_interval_ms.load(Ordering::SeqCst) as u64;
+                    thread::sleep(time::Duration::from_millis(interval));
                 }
             });
         }
@@ -281,6 +648,19 @@ impl webrender_traits::VRCompositorHandler for WebVRCompositorHandler {
                     }
                 }
             }
+            webrender_traits::VRCompositorCommand::CaptureFrame(compositor_id, sender) => {
+                // Reads back the GPU texture last handed to SubmitFrame, so
+                // tools/tests can inspect what a VR page actually rendered
+                // without a headset-side capture. Must run here, on the
+                // compositor thread, since that's where the GL context
+                // submit_frame() used is current.
+                if let Some(compositor) = self.compositors.get(&compositor_id) {
+                    let frame = unsafe { (**compositor).capture_frame() };
+                    let _result = sender.send(Ok(frame));
+                } else {
+                    let _result = sender.send(Err(()));
+                }
+            }
             webrender_traits::VRCompositorCommand::Release(compositor_id) => {
                 self.compositors.remove(&compositor_id);
             }
@@ -310,4 +690,85 @@ impl WebVRCompositorHandler {
             }
         };
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    // A WebVRThread with the mock backend enabled, plus the receiving end of
+    // its constellation channel so tests can observe the events it sends.
+    fn mock_thread() -> (WebVRThread, mpsc::Receiver<ConstellationMsg>) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let (constellation_sender, constellation_receiver) = mpsc::channel();
+        let mut thread = WebVRThread::new(receiver, sender, constellation_sender);
+        thread.mock_enabled = true;
+        (thread, constellation_receiver)
+    }
+
+    fn mock_display_data(display_id: u64) -> VRDisplayData {
+        VRDisplayData {
+            display_id: display_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mock_create_display_is_returned_by_get_displays() {
+        let (mut thread, _constellation_receiver) = mock_thread();
+        thread.handle_mock_create_display(mock_display_data(1));
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        thread.handle_get_displays(sender);
+        let displays = receiver.recv().unwrap().unwrap();
+        assert_eq!(displays.len(), 1);
+        assert_eq!(displays[0].display_id, 1);
+    }
+
+    // Pulls the single VRDisplayEvent out of the next WebVREvent sent to the
+    // constellation channel, panicking with a plain message (no Debug
+    // formatting of external msg types we don't control) if anything else
+    // comes through.
+    fn recv_display_event(receiver: &mpsc::Receiver<ConstellationMsg>) -> VRDisplayEvent {
+        match receiver.try_recv() {
+            Ok(ConstellationMsg::WebVREvent(_, WebVREventMsg::DisplayEvent(event))) => event,
+            Ok(_) => panic!("expected a WebVREvent::DisplayEvent"),
+            Err(_) => panic!("expected a WebVREvent::DisplayEvent, got nothing"),
+        }
+    }
+
+    #[test]
+    fn test_poll_events_synthesizes_connect_and_disconnect() {
+        let (mut thread, constellation_receiver) = mock_thread();
+        thread.handle_mock_create_display(mock_display_data(1));
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        thread.poll_events(sender);
+        receiver.recv().unwrap();
+        match recv_display_event(&constellation_receiver) {
+            VRDisplayEvent::Connect(data) => assert_eq!(data.display_id, 1),
+            _ => panic!("expected a Connect event"),
+        }
+
+        thread.mock_displays.remove(&1);
+        let (sender, receiver) = ipc::channel().unwrap();
+        thread.poll_events(sender);
+        receiver.recv().unwrap();
+        match recv_display_event(&constellation_receiver) {
+            VRDisplayEvent::Disconnect(display_id) => assert_eq!(display_id, 1),
+            _ => panic!("expected a Disconnect event"),
+        }
+    }
+
+    #[test]
+    fn test_capture_frame_unknown_compositor_errors() {
+        // No Create command was ever sent for this id, so there's no raw
+        // VRDevice pointer behind it to read a frame from -- exercising this
+        // without real hardware is only possible via that absence.
+        let mut handler = WebVRCompositorHandler::new();
+        let (sender, receiver) = ipc::channel().unwrap();
+        handler.handle(webrender_traits::VRCompositorCommand::CaptureFrame(0, sender), None);
+        assert_eq!(receiver.recv().unwrap(), Err(()));
+    }
 }
\ No newline at end of file
@@ -5,15 +5,65 @@ use msg::constellation_msg::PipelineId;
 
 pub type WebVRResult<T> = Result<T, String>;
 
+// A VR motion controller, surfaced to script as a Gamepad. Its per-frame
+// WebVRGamepadState carries a VRPose of the same shape as the headset's, so
+// the DOM layer can funnel both through the same VRPose::update path.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct VRGamepadData {
+    pub gamepad_id: u64,
+    pub display_id: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct VRGamepadButton {
+    pub pressed: bool,
+    pub touched: bool,
+    pub value: f64,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct WebVRGamepadState {
+    pub gamepad_id: u64,
+    pub connected: bool,
+    pub timestamp: f64,
+    pub axes: Vec<f64>,
+    pub buttons: Vec<VRGamepadButton>,
+    pub pose: VRPose,
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum WebVRMsg {
     RegisterContext(PipelineId),
     UnregisterContext(PipelineId),
-    PollEvents(IpcSender<bool>),
+    PollEvents(IpcSender<()>),
     GetVRDisplays(IpcSender<WebVRResult<Vec<VRDisplayData>>>),
     GetFrameData(PipelineId, u64, f64, f64, IpcSender<WebVRResult<VRFrameData>>),
     ResetPose(PipelineId, u64, IpcSender<WebVRResult<VRDisplayData>>),
-    RequestPresent(PipelineId, u64, IpcSender<WebVRResult<()>>),
+    RequestPresent(PipelineId, u64, Vec<VRLayer>, IpcSender<WebVRResult<()>>),
     ExitPresent(PipelineId, u64, Option<IpcSender<WebVRResult<()>>>),
+    SubmitFrame(PipelineId, u64, u32, VRLayer, IpcSender<WebVRResult<()>>),
+    GetGamepads(IpcSender<WebVRResult<Vec<VRGamepadData>>>),
+    GetGamepadState(PipelineId, u64, IpcSender<WebVRResult<WebVRGamepadState>>),
+    VibrateHapticGamepad(PipelineId, u64, u32, f64, IpcSender<WebVRResult<()>>),
+    // Signals that a presenting display's page wants its rAF callbacks paced
+    // independently from the window's own rAF queue. No reply: while any
+    // display has vsync requested, the WebVR thread switches its single shared
+    // event-poll period to a fixed ~90Hz interval; the period does not follow
+    // the display's own refresh rate.
+    RequestVSync(PipelineId, u64),
+    CancelVSync(u64),
+    // Ordered (x, z) boundary points of the play area's chaperone polygon, for
+    // backends that report one. None if the backend (or the mock backend,
+    // when no polygon was puppeted) only has a sizeX/sizeZ rectangle to offer.
+    GetStageBounds(PipelineId, u64, IpcSender<WebVRResult<Option<Vec<(f32, f32)>>>>),
+    // Puppet display used by automated tests. The device is created fully-formed
+    // by the test harness, so no reply channel is needed: its display_id is
+    // chosen by the caller and echoed back by GetVRDisplays like a real device.
+    MockCreateDisplay(VRDisplayData),
+    MockSetFrameData(u64, VRFrameData),
+    MockSetPose(u64, Option<VRPose>),
+    MockSetStageParameters(u64, VRStageParameters, Option<Vec<(f32, f32)>>),
+    MockFireEvent(u64, VRDisplayEvent),
     Exit,
 }